@@ -1,7 +1,11 @@
 #[cfg(any(feature = "async-std", feature = "tokio"))]
 mod lock;
 
-#[cfg(all(feature = "async-std", not(feature = "tokio")))]
+#[cfg(all(any(feature = "async-std", feature = "tokio"), not(feature = "tokio-comp")))]
 pub use crate::lock::LockGuard;
+#[cfg(feature = "tokio-comp")]
+pub use crate::lock::LockGuard;
+#[cfg(feature = "tokio-comp")]
+pub use crate::lock::LeaseGuard;
 #[cfg(any(feature = "async-std", feature = "tokio"))]
 pub use crate::lock::{Lock, LockError, LockManager};
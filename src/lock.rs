@@ -1,11 +1,16 @@
 use std::io;
+#[cfg(feature = "tokio-comp")]
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 use futures::future::join_all;
-use futures::Future;
+use futures::{Future, StreamExt};
 use rand::{thread_rng, Rng, RngCore};
+use redis::aio::ConnectionManager;
 use redis::Value::Okay;
 use redis::{Client, IntoConnectionInfo, RedisResult, Value};
+use tokio::sync::OnceCell;
 
 const DEFAULT_RETRY_COUNT: u32 = 3;
 const DEFAULT_RETRY_DELAY: u32 = 200;
@@ -34,19 +39,63 @@ pub enum LockError {
     Io(io::Error),
     Redis(redis::RedisError),
     Unavailable,
+    /// `LockManager` has no servers configured, so quorum can never be reached.
+    NoServers,
+}
+
+impl std::fmt::Display for LockError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LockError::Io(e) => write!(f, "io error: {}", e),
+            LockError::Redis(e) => write!(f, "redis error: {}", e),
+            LockError::Unavailable => write!(f, "unable to reach quorum"),
+            LockError::NoServers => write!(f, "no servers configured"),
+        }
+    }
+}
+
+impl std::error::Error for LockError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            LockError::Io(e) => Some(e),
+            LockError::Redis(e) => Some(e),
+            LockError::Unavailable | LockError::NoServers => None,
+        }
+    }
 }
 
 /// The lock manager.
 ///
 /// Implements the necessary functionality to acquire and release locks
 /// and handles the Redis connections.
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct LockManager {
     /// List of all Redis clients
     pub servers: Vec<Client>,
+    /// Lazily-established, multiplexed connection per server. Reused across
+    /// lock/extend/unlock calls instead of dialing a fresh connection each time.
+    /// `ConnectionManager` reconnects transparently on error, so a failed
+    /// connection simply gets re-dialed on the next call rather than poisoning
+    /// the cell.
+    connections: Vec<Arc<OnceCell<ConnectionManager>>>,
     quorum: u32,
     retry_count: u32,
     retry_delay: u32,
+    fencing_tokens: bool,
+    server_time: bool,
+}
+
+impl std::fmt::Debug for LockManager {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LockManager")
+            .field("servers", &self.servers)
+            .field("quorum", &self.quorum)
+            .field("retry_count", &self.retry_count)
+            .field("retry_delay", &self.retry_delay)
+            .field("fencing_tokens", &self.fencing_tokens)
+            .field("server_time", &self.server_time)
+            .finish()
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -58,6 +107,13 @@ pub struct Lock<'a> {
     /// Time the lock is still valid.
     /// Should only be slightly smaller than the requested TTL.
     pub validity_time: usize,
+    /// Monotonically increasing fencing token for this resource, present
+    /// when the `LockManager` was configured via `set_fencing_tokens`.
+    /// Sourced from a single designated server so it's strictly increasing
+    /// across successive holders, even if they won quorum against different
+    /// subsets of instances. Downstream services should reject writes
+    /// stamped with a token smaller than one they've already seen.
+    pub fencing_token: Option<u64>,
     /// Used to limit the lifetime of a lock to its lock manager.
     pub lock_manager: &'a LockManager,
 }
@@ -68,10 +124,17 @@ pub struct LockGuard<'a> {
     pub lock: Lock<'a>,
 }
 
+#[cfg(not(feature = "tokio-comp"))]
+impl<'a> LockGuard<'a> {
+    fn new(lock: Lock<'a>) -> Self {
+        LockGuard { lock }
+    }
+}
 
-// Dropping this guard inside the context of a tokio runtime if tokio-comp is enabled 
-// will block the tokio runtime. 
-// Because of this, the guard is not compiled if tokio-comp is enabled. 
+// Dropping this guard inside the context of a tokio runtime if tokio-comp is enabled
+// will block the tokio runtime.
+// Because of this, this blocking guard is not compiled if tokio-comp is enabled;
+// the non-blocking `LockGuard` below takes its place.
 #[cfg(not(feature = "tokio-comp"))]
 impl Drop for LockGuard<'_> {
     fn drop(&mut self) {
@@ -79,25 +142,185 @@ impl Drop for LockGuard<'_> {
     }
 }
 
+// Under tokio-comp, blocking the runtime in `Drop` (as the non-tokio guard does)
+// would stall the executor, so this guard releases the lock without blocking:
+// prefer calling `release` directly, and fall back to spawning the unlock
+// fan-out onto the current runtime on drop.
+#[cfg(feature = "tokio-comp")]
+#[derive(Debug, Clone)]
+pub struct LockGuard<'a> {
+    pub lock: Lock<'a>,
+    released: bool,
+}
+
+#[cfg(feature = "tokio-comp")]
+impl<'a> LockGuard<'a> {
+    fn new(lock: Lock<'a>) -> Self {
+        LockGuard {
+            lock,
+            released: false,
+        }
+    }
+
+    /// Release the lock, awaiting the unlock fan-out to all instances.
+    ///
+    /// Prefer this to letting the guard simply drop: `Drop` cannot await, so
+    /// it only makes a best-effort attempt to spawn the same unlock on the
+    /// current runtime. Marks the guard as released so `Drop` doesn't also
+    /// spawn a second, redundant unlock.
+    pub async fn release(mut self) {
+        self.lock.lock_manager.unlock(&self.lock).await;
+        self.released = true;
+    }
+}
+
+#[cfg(feature = "tokio-comp")]
+impl Drop for LockGuard<'_> {
+    fn drop(&mut self) {
+        if self.released {
+            return;
+        }
+
+        let lock_manager = self.lock.lock_manager.clone();
+        let resource = self.lock.resource.clone();
+        let val = self.lock.val.clone();
+        let validity_time = self.lock.validity_time;
+
+        if let Ok(handle) = tokio::runtime::Handle::try_current() {
+            handle.spawn(async move {
+                let lock = Lock {
+                    lock_manager: &lock_manager,
+                    resource,
+                    val,
+                    validity_time,
+                    fencing_token: None,
+                };
+                lock_manager.unlock(&lock).await;
+            });
+        }
+    }
+}
+
+/// Guard returned by [`LockManager::acquire_with_lease`]. Holds the lock
+/// while a background watchdog keeps extending it, so the critical section
+/// can run arbitrarily long on top of a short TTL.
+///
+/// Dropping the guard stops the watchdog and releases the resource, the
+/// same way [`LockGuard`]'s `Drop` does: a best-effort unlock spawned onto
+/// the current runtime, since `Drop` can't await. Prefer `release` for a
+/// guaranteed single unlock when you can await it.
+#[cfg(feature = "tokio-comp")]
+pub struct LeaseGuard<'a> {
+    pub lock: Lock<'a>,
+    valid: Arc<AtomicBool>,
+    stop: Arc<tokio::sync::Notify>,
+    watchdog: Option<tokio::task::JoinHandle<()>>,
+    released: bool,
+}
+
+#[cfg(feature = "tokio-comp")]
+impl LeaseGuard<'_> {
+    /// Whether the watchdog still believes the lock is held. Flips to
+    /// `false` once an extend fails to reach quorum.
+    pub fn is_valid(&self) -> bool {
+        self.valid.load(Ordering::SeqCst)
+    }
+
+    /// Stop the watchdog and release the lock, awaiting the unlock fan-out
+    /// to all instances.
+    ///
+    /// Prefer this to letting the guard simply drop: `Drop` cannot await, so
+    /// it only makes a best-effort attempt to spawn the same unlock on the
+    /// current runtime.
+    pub async fn release(mut self) {
+        self.stop.notify_one();
+        if let Some(handle) = self.watchdog.take() {
+            handle.abort();
+        }
+        self.lock.lock_manager.unlock(&self.lock).await;
+        self.released = true;
+    }
+}
+
+#[cfg(feature = "tokio-comp")]
+impl Drop for LeaseGuard<'_> {
+    fn drop(&mut self) {
+        self.stop.notify_one();
+        if let Some(handle) = self.watchdog.take() {
+            handle.abort();
+        }
+
+        if self.released {
+            return;
+        }
+
+        let lock_manager = self.lock.lock_manager.clone();
+        let resource = self.lock.resource.clone();
+        let val = self.lock.val.clone();
+        let validity_time = self.lock.validity_time;
+
+        if let Ok(handle) = tokio::runtime::Handle::try_current() {
+            handle.spawn(async move {
+                let lock = Lock {
+                    lock_manager: &lock_manager,
+                    resource,
+                    val,
+                    validity_time,
+                    fencing_token: None,
+                };
+                lock_manager.unlock(&lock).await;
+            });
+        }
+    }
+}
+
 impl LockManager {
     /// Create a new lock manager instance, defined by the given Redis connection uris.
     /// Quorum is defined to be N/2+1, with N being the number of given Redis instances.
     ///
     /// Sample URI: `"redis://127.0.0.1:6379"`
+    ///
+    /// # Panics
+    ///
+    /// Panics if any uri fails to parse into Redis connection info. Use
+    /// [`LockManager::try_new`] to handle invalid URIs without panicking.
     pub fn new<T: AsRef<str> + IntoConnectionInfo>(uris: Vec<T>) -> LockManager {
+        Self::try_new(uris).expect("invalid Redis connection URI")
+    }
+
+    /// Fallible counterpart to `new`: returns a `LockError::Redis` instead of
+    /// panicking if a uri fails to parse into Redis connection info.
+    pub fn try_new<T: AsRef<str> + IntoConnectionInfo>(
+        uris: Vec<T>,
+    ) -> Result<LockManager, LockError> {
         let quorum = (uris.len() as u32) / 2 + 1;
 
-        let servers: Vec<Client> = uris
-            .into_iter()
-            .map(|uri| Client::open(uri).unwrap())
-            .collect();
+        let mut servers = Vec::with_capacity(uris.len());
+        for uri in uris {
+            servers.push(Client::open(uri).map_err(LockError::Redis)?);
+        }
+        let connections = servers.iter().map(|_| Arc::new(OnceCell::new())).collect();
 
-        LockManager {
+        Ok(LockManager {
             servers,
+            connections,
             quorum,
             retry_count: DEFAULT_RETRY_COUNT,
             retry_delay: DEFAULT_RETRY_DELAY,
-        }
+            fencing_tokens: false,
+            server_time: false,
+        })
+    }
+
+    /// Get the multiplexed connection for the instance at `idx`, establishing
+    /// it on first use. The returned `ConnectionManager` is a cheap handle
+    /// onto the shared connection and reconnects transparently on error, so
+    /// callers never need to dial again themselves.
+    async fn connection(&self, idx: usize) -> Result<ConnectionManager, redis::RedisError> {
+        self.connections[idx]
+            .get_or_try_init(|| async { ConnectionManager::new(self.servers[idx].clone()).await })
+            .await
+            .cloned()
     }
 
     /// Get 20 random bytes from the pseudorandom interface.
@@ -118,16 +341,76 @@ impl LockManager {
         self.retry_delay = delay;
     }
 
+    /// Enable or disable fencing tokens.
+    ///
+    /// When enabled, each successful lock acquisition (`lock()` or
+    /// `wait_for_lock()`) additionally increments a per-resource counter on
+    /// a single designated server (the first configured one) and returns
+    /// the new value as `Lock::fencing_token`, so downstream services can
+    /// reject writes stamped with a stale (smaller) token.
+    ///
+    /// Taking the token from independent per-instance counters instead
+    /// would not guarantee it's strictly increasing: two holders can win
+    /// quorum against different, overlapping subsets of instances, and
+    /// their local counters can disagree. Routing every token through one
+    /// server fixes that, at the cost of making that server a single point
+    /// of failure for acquisitions: if it can't be reached, the call fails
+    /// even if quorum was otherwise won. Disabled by default.
+    pub fn set_fencing_tokens(&mut self, enabled: bool) {
+        self.fencing_tokens = enabled;
+    }
+
+    /// Key used to store the monotonically increasing fencing token counter
+    /// for a given resource.
+    fn fencing_token_key(resource: &[u8]) -> Vec<u8> {
+        let mut key = resource.to_vec();
+        key.extend_from_slice(b":fencing_token");
+        key
+    }
+
+    /// Use the Redis server's own clock, via the `TIME` command, instead of
+    /// the local wall clock to compute lock validity.
+    ///
+    /// Implemented as two extra `TIME` round-trips to the first configured
+    /// server, bracketing the whole instance fan-out, rather than a single
+    /// server-side timestamp recorded atomically alongside the lock value
+    /// by the acquisition script itself. That would measure only the `SET`;
+    /// this measures the whole fan-out (every instance, not just the
+    /// fastest or the first), which is the more conservative of the two —
+    /// it only ever reports *more* elapsed time, never less. The tradeoff
+    /// is the extra round-trips on every lock/extend call, and the reading
+    /// coming from one server rather than being derived locklessly from the
+    /// lock write itself. Disabled by default; falls back to the
+    /// client-side estimate if either `TIME` call fails.
+    pub fn set_server_time(&mut self, enabled: bool) {
+        self.server_time = enabled;
+    }
+
+    /// Query the server's current time as `(seconds, microseconds)`.
+    async fn server_time(&self, idx: usize) -> RedisResult<(i64, i64)> {
+        let mut con = self.connection(idx).await?;
+        redis::cmd("TIME").query_async(&mut con).await
+    }
+
+    /// Milliseconds elapsed between two server-reported `TIME` readings.
+    fn server_elapsed_ms(start: (i64, i64), end: (i64, i64)) -> usize {
+        let micros = (end.0 - start.0) * 1_000_000 + (end.1 - start.1);
+        (micros.max(0) / 1000) as usize
+    }
+
+    /// Milliseconds elapsed since `start`, measured by the local clock.
+    fn local_elapsed_ms(start: Instant) -> usize {
+        start.elapsed().as_millis() as usize
+    }
+
     async fn lock_instance(
-        client: &redis::Client,
+        &self,
+        idx: usize,
         resource: &[u8],
         val: Vec<u8>,
         ttl: usize,
-    ) -> bool {
-        let mut con = match client.get_async_connection().await {
-            Err(_) => return false,
-            Ok(val) => val,
-        };
+    ) -> Result<bool, LockError> {
+        let mut con = self.connection(idx).await.map_err(LockError::Redis)?;
         let result: RedisResult<Value> = redis::cmd("SET")
             .arg(resource)
             .arg(val)
@@ -138,21 +421,33 @@ impl LockManager {
             .await;
 
         match result {
-            Ok(Okay) => true,
-            Ok(_) | Err(_) => false,
+            Ok(Okay) => Ok(true),
+            Ok(_) => Ok(false),
+            Err(e) => Err(LockError::Redis(e)),
         }
     }
 
+    /// Issue the next fencing token for `resource`, always from the first
+    /// configured server. Using one fixed server as the counting authority,
+    /// rather than whichever instances happened to make up a quorum, is
+    /// what makes the result strictly increasing across successive holders.
+    async fn fencing_token_instance(&self, resource: &[u8]) -> Result<u64, LockError> {
+        let mut con = self.connection(0).await.map_err(LockError::Redis)?;
+        redis::cmd("INCR")
+            .arg(Self::fencing_token_key(resource))
+            .query_async(&mut con)
+            .await
+            .map_err(LockError::Redis)
+    }
+
     async fn extend_lock_instance(
-        client: &redis::Client,
+        &self,
+        idx: usize,
         resource: &[u8],
         val: &[u8],
         ttl: usize,
-    ) -> bool {
-        let mut con = match client.get_async_connection().await {
-            Err(_) => return false,
-            Ok(val) => val,
-        };
+    ) -> Result<bool, LockError> {
+        let mut con = self.connection(idx).await.map_err(LockError::Redis)?;
         let script = redis::Script::new(EXTEND_SCRIPT);
         let result: RedisResult<i32> = script
             .key(resource)
@@ -161,13 +456,13 @@ impl LockManager {
             .invoke_async(&mut con)
             .await;
         match result {
-            Ok(val) => val == 1,
-            Err(_) => false,
+            Ok(val) => Ok(val == 1),
+            Err(e) => Err(LockError::Redis(e)),
         }
     }
 
-    async fn unlock_instance(client: &redis::Client, resource: &[u8], val: &[u8]) -> bool {
-        let mut con = match client.get_async_connection().await {
+    async fn unlock_instance(&self, idx: usize, resource: &[u8], val: &[u8]) -> bool {
+        let mut con = match self.connection(idx).await {
             Err(_) => return false,
             Ok(val) => val,
         };
@@ -179,53 +474,145 @@ impl LockManager {
         }
     }
 
-    // Can be used for creating or extending a lock
+    /// A single quorum round: dispatch `lock` to every instance once, and
+    /// either return the acquired `Lock` or unlock whatever was acquired and
+    /// report why (no servers configured, a genuine connectivity error, or
+    /// plain quorum failure).
+    async fn try_once<'a, T, Fut>(
+        &'a self,
+        resource: &[u8],
+        value: &[u8],
+        ttl: usize,
+        lock: &T,
+    ) -> Result<Lock<'a>, LockError>
+    where
+        T: Fn(usize) -> Fut,
+        Fut: Future<Output = Result<bool, LockError>>,
+    {
+        if self.servers.is_empty() {
+            return Err(LockError::NoServers);
+        }
+
+        // Brackets the whole fan-out below, not just the `SET`s: see
+        // `set_server_time`'s doc comment for why that's the deliberate,
+        // more conservative choice.
+        let start_time = Instant::now();
+        let server_start = if self.server_time {
+            self.server_time(0).await.ok()
+        } else {
+            None
+        };
+
+        let mut n = 0u32;
+        let mut last_error = None;
+        for result in join_all((0..self.servers.len()).map(lock)).await {
+            match result {
+                Ok(true) => n += 1,
+                Ok(false) => {}
+                Err(e) => last_error = Some(e),
+            }
+        }
+
+        let elapsed_ms = match server_start {
+            Some(start) => match self.server_time(0).await {
+                Ok(end) => Self::server_elapsed_ms(start, end),
+                Err(_) => Self::local_elapsed_ms(start_time),
+            },
+            None => Self::local_elapsed_ms(start_time),
+        };
+
+        let drift = (ttl as f32 * CLOCK_DRIFT_FACTOR) as usize + 2;
+        let validity_time = ttl.saturating_sub(drift).saturating_sub(elapsed_ms);
+
+        if n >= self.quorum && validity_time > 0 {
+            Ok(Lock {
+                lock_manager: self,
+                resource: resource.to_vec(),
+                val: value.to_vec(),
+                validity_time,
+                fencing_token: None,
+            })
+        } else {
+            join_all((0..self.servers.len()).map(|idx| self.unlock_instance(idx, resource, value)))
+                .await;
+            Err(last_error.unwrap_or(LockError::Unavailable))
+        }
+    }
+
+    /// Like `try_once`, but also attaches a fencing token when
+    /// `fencing_tokens` is enabled. If the token can't be obtained, the
+    /// quorum just won is released rather than handed back without one, so
+    /// callers never see a `Lock` that silently skipped fencing.
+    async fn try_once_with_fencing<'a, T, Fut>(
+        &'a self,
+        resource: &[u8],
+        value: &[u8],
+        ttl: usize,
+        lock: &T,
+    ) -> Result<Lock<'a>, LockError>
+    where
+        T: Fn(usize) -> Fut,
+        Fut: Future<Output = Result<bool, LockError>>,
+    {
+        let mut acquired = self.try_once(resource, value, ttl, lock).await?;
+
+        if self.fencing_tokens {
+            match self.fencing_token_instance(resource).await {
+                Ok(token) => acquired.fencing_token = Some(token),
+                Err(e) => {
+                    self.unlock(&acquired).await;
+                    return Err(e);
+                }
+            }
+        }
+
+        Ok(acquired)
+    }
+
+    // Can be used for creating or extending a lock. `apply_fencing` gates
+    // whether a won quorum also mints a fencing token: extending an
+    // already-held lock isn't a new holder, so `extend` passes `false` to
+    // avoid bumping the counter on every renewal.
     async fn exec_or_retry<'a, T, Fut>(
         &'a self,
         resource: &[u8],
         value: &[u8],
         ttl: usize,
+        apply_fencing: bool,
         lock: T,
     ) -> Result<Lock<'a>, LockError>
     where
-        T: Fn(&'a Client) -> Fut,
-        Fut: Future<Output = bool>,
+        T: Fn(usize) -> Fut,
+        Fut: Future<Output = Result<bool, LockError>>,
     {
+        let mut last_error = None;
+
         for _ in 0..self.retry_count {
-            let start_time = Instant::now();
-            let n = join_all(self.servers.iter().map(&lock))
-                .await
-                .into_iter()
-                .fold(0, |count, locked| if locked { count + 1 } else { count });
-
-            let drift = (ttl as f32 * CLOCK_DRIFT_FACTOR) as usize + 2;
-            let elapsed = start_time.elapsed();
-            let validity_time = ttl
-                - drift
-                - elapsed.as_secs() as usize * 1000
-                - elapsed.subsec_nanos() as usize / 1_000_000;
-
-            if n >= self.quorum && validity_time > 0 {
-                return Ok(Lock {
-                    lock_manager: self,
-                    resource: resource.to_vec(),
-                    val: value.to_vec(),
-                    validity_time,
-                });
+            let attempt = if apply_fencing {
+                self.try_once_with_fencing(resource, value, ttl, &lock).await
             } else {
-                join_all(
-                    self.servers
-                        .iter()
-                        .map(|client| Self::unlock_instance(client, resource, value)),
-                )
-                .await;
+                self.try_once(resource, value, ttl, &lock).await
+            };
+
+            match attempt {
+                Ok(lock) => return Ok(lock),
+                Err(LockError::NoServers) => return Err(LockError::NoServers),
+                Err(e) => last_error = Some(e),
             }
 
-            let n = thread_rng().gen_range(0..self.retry_delay);
-            tokio::time::sleep(Duration::from_millis(u64::from(n))).await
+            tokio::time::sleep(Self::random_backoff(self.retry_delay)).await;
         }
 
-        Err(LockError::Unavailable)
+        Err(last_error.unwrap_or(LockError::Unavailable))
+    }
+
+    /// A random backoff in `[0, retry_delay)` milliseconds, or zero
+    /// immediately if `retry_delay` is `0`.
+    fn random_backoff(retry_delay: u32) -> Duration {
+        if retry_delay == 0 {
+            return Duration::ZERO;
+        }
+        Duration::from_millis(u64::from(thread_rng().gen_range(0..retry_delay)))
     }
 
     /// Unlock the given lock.
@@ -234,9 +621,7 @@ impl LockManager {
     /// and remove the key.
     pub async fn unlock(&self, lock: &Lock<'_>) {
         join_all(
-            self.servers
-                .iter()
-                .map(|client| Self::unlock_instance(client, &lock.resource, &lock.val)),
+            (0..self.servers.len()).map(|idx| self.unlock_instance(idx, &lock.resource, &lock.val)),
         )
         .await;
     }
@@ -251,8 +636,8 @@ impl LockManager {
     pub async fn lock<'a>(&'a self, resource: &[u8], ttl: usize) -> Result<Lock<'a>, LockError> {
         let val = self.get_unique_lock_id().unwrap();
 
-        self.exec_or_retry(resource, &val.clone(), ttl, move |client| {
-            Self::lock_instance(client, resource, val.clone(), ttl)
+        self.exec_or_retry(resource, &val.clone(), ttl, true, move |idx| {
+            self.lock_instance(idx, resource, val.clone(), ttl)
         })
         .await
     }
@@ -260,27 +645,194 @@ impl LockManager {
     /// Loops until the lock is acquired.
     ///
     /// The lock is placed in a guard that will unlock the lock when the guard is dropped.
-    #[cfg(not(feature = "tokio-comp"))]
-    pub async fn acquire<'a>(&'a self, resource: &[u8], ttl: usize) -> LockGuard<'a> {
-        let lock = self.acquire_no_guard(resource, ttl).await;
-        LockGuard{lock}
+    ///
+    /// # Errors
+    ///
+    /// Returns `LockError::NoServers` immediately rather than looping
+    /// forever: with no servers configured, quorum can never be reached, so
+    /// retrying would just spin.
+    pub async fn acquire<'a>(&'a self, resource: &[u8], ttl: usize) -> Result<LockGuard<'a>, LockError> {
+        let lock = self.acquire_no_guard(resource, ttl).await?;
+        Ok(LockGuard::new(lock))
     }
 
     /// Loops until the lock is acquired.
-    pub async fn acquire_no_guard<'a>(&'a self, resource: &[u8], ttl: usize) -> Lock<'a> {
+    ///
+    /// # Errors
+    ///
+    /// Returns `LockError::NoServers` immediately rather than looping
+    /// forever: with no servers configured, quorum can never be reached, so
+    /// retrying would just spin without the backoff sleep `lock()`'s other
+    /// error paths go through.
+    pub async fn acquire_no_guard<'a>(&'a self, resource: &[u8], ttl: usize) -> Result<Lock<'a>, LockError> {
         loop {
-            if let Ok(lock) = self.lock(resource, ttl).await {
-                return lock;
+            match self.lock(resource, ttl).await {
+                Ok(lock) => return Ok(lock),
+                Err(LockError::NoServers) => return Err(LockError::NoServers),
+                Err(_) => {}
             }
         }
     }
 
-    /// Extend the given lock by given time in milliseconds
-    pub async fn extend<'a>(&'a self, lock: &Lock<'a>, ttl: usize) -> Result<Lock<'a>, LockError> {
-        self.exec_or_retry(&lock.resource, &lock.val, ttl, move |client| {
-            Self::extend_lock_instance(client, &lock.resource, &lock.val, ttl)
+    /// Like `acquire_no_guard`, but between attempts waits for whichever
+    /// comes first: a Redis keyspace expire/delete event on the resource key
+    /// or the usual random backoff. An event only cuts the wait short; it
+    /// does not replace the backoff, so a new attempt is made at least once
+    /// per backoff interval even when notifications are enabled. If
+    /// keyspace notifications (`notify-keyspace-events`) aren't enabled on
+    /// the server, no event arrives and this is the plain retry/backoff loop.
+    ///
+    /// Honors `set_fencing_tokens` the same way `lock()` does.
+    ///
+    /// Returns `LockError::Unavailable` if `timeout` elapses before the
+    /// lock is acquired.
+    pub async fn wait_for_lock<'a>(
+        &'a self,
+        resource: &[u8],
+        ttl: usize,
+        timeout: Duration,
+    ) -> Result<Lock<'a>, LockError> {
+        tokio::time::timeout(timeout, async {
+            loop {
+                let val = self.get_unique_lock_id().unwrap();
+                if let Ok(lock) = self
+                    .try_once_with_fencing(resource, &val.clone(), ttl, &move |idx| {
+                        self.lock_instance(idx, resource, val.clone(), ttl)
+                    })
+                    .await
+                {
+                    return lock;
+                }
+
+                tokio::select! {
+                    _ = tokio::time::sleep(Self::random_backoff(self.retry_delay)) => {}
+                    _ = self.wait_for_release_notification(resource) => {}
+                }
+            }
         })
         .await
+        .map_err(|_| LockError::Unavailable)
+    }
+
+    /// Waits for a `__keyevent@{db}__:expired` or `__keyevent@{db}__:del`
+    /// event naming `resource`, subscribing on the first configured
+    /// server's logical database only. A release that happens to be
+    /// observed on a different instance (e.g. the first server is down, or
+    /// it simply wasn't part of the quorum that held the lock) is *not*
+    /// seen by this subscription — it's best-effort, and callers are only
+    /// saved from waiting out the full backoff by luck, not by correctness.
+    /// Never resolves if notifications aren't enabled or the subscription
+    /// can't be established, so callers should race it against a backoff
+    /// sleep rather than await it alone.
+    async fn wait_for_release_notification(&self, resource: &[u8]) {
+        let Some(client) = self.servers.first() else {
+            return futures::future::pending().await;
+        };
+        let db = client.get_connection_info().redis.db;
+        let Ok(conn) = client.get_async_connection().await else {
+            return futures::future::pending().await;
+        };
+        let mut pubsub = conn.into_pubsub();
+        if pubsub
+            .psubscribe(format!("__keyevent@{}__:expired", db))
+            .await
+            .is_err()
+            || pubsub
+                .psubscribe(format!("__keyevent@{}__:del", db))
+                .await
+                .is_err()
+        {
+            return futures::future::pending().await;
+        }
+
+        let mut messages = pubsub.on_message();
+        while let Some(msg) = messages.next().await {
+            if let Ok(payload) = msg.get_payload::<Vec<u8>>() {
+                if payload == resource {
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Acquire the lock and spawn a background watchdog that extends it
+    /// every `ttl / 3` until the returned [`LeaseGuard`] is dropped.
+    ///
+    /// This lets callers take a short TTL (bounding how long a dead holder
+    /// can block others) while still safely holding the lock across
+    /// arbitrarily long work. If an extend fails to reach quorum, the
+    /// watchdog stops renewing, flips [`LeaseGuard::is_valid`] to `false`,
+    /// and invokes `on_lost` (if given) so the caller can react instead of
+    /// silently running past an expired lock.
+    ///
+    /// # Errors
+    ///
+    /// Returns `LockError::NoServers` immediately rather than looping
+    /// forever: with no servers configured, quorum can never be reached, so
+    /// retrying would just spin.
+    #[cfg(feature = "tokio-comp")]
+    pub async fn acquire_with_lease<'a>(
+        &'a self,
+        resource: &[u8],
+        ttl: usize,
+        on_lost: Option<Box<dyn Fn() + Send + Sync>>,
+    ) -> Result<LeaseGuard<'a>, LockError> {
+        let lock = self.acquire_no_guard(resource, ttl).await?;
+
+        let valid = Arc::new(AtomicBool::new(true));
+        let stop = Arc::new(tokio::sync::Notify::new());
+        let watchdog = {
+            let lock_manager = self.clone();
+            let resource = lock.resource.clone();
+            let val = lock.val.clone();
+            let valid = valid.clone();
+            let stop = stop.clone();
+            let interval = Duration::from_millis((ttl / 3) as u64);
+
+            tokio::spawn(async move {
+                loop {
+                    tokio::select! {
+                        _ = tokio::time::sleep(interval) => {}
+                        _ = stop.notified() => break,
+                    }
+
+                    let current = Lock {
+                        lock_manager: &lock_manager,
+                        resource: resource.clone(),
+                        val: val.clone(),
+                        validity_time: ttl,
+                        fencing_token: None,
+                    };
+
+                    if lock_manager.extend(&current, ttl).await.is_err() {
+                        valid.store(false, Ordering::SeqCst);
+                        if let Some(on_lost) = &on_lost {
+                            on_lost();
+                        }
+                        break;
+                    }
+                }
+            })
+        };
+
+        Ok(LeaseGuard {
+            lock,
+            valid,
+            stop,
+            watchdog: Some(watchdog),
+            released: false,
+        })
+    }
+
+    /// Extend the given lock by given time in milliseconds
+    pub async fn extend<'a>(&'a self, lock: &Lock<'a>, ttl: usize) -> Result<Lock<'a>, LockError> {
+        let mut extended = self
+            .exec_or_retry(&lock.resource, &lock.val, ttl, false, move |idx| {
+                self.extend_lock_instance(idx, &lock.resource, &lock.val, ttl)
+            })
+            .await?;
+        extended.fencing_token = lock.fencing_token;
+        Ok(extended)
     }
 }
 
@@ -322,7 +874,6 @@ mod tests {
         is_normal::<LockManager>();
         is_normal::<LockError>();
         is_normal::<Lock>();
-        #[cfg(not(feature = "tokio-comp"))]
         is_normal::<LockGuard>();
     }
 
@@ -358,6 +909,82 @@ mod tests {
         assert_eq!(2, rl.quorum);
     }
 
+    #[test]
+    fn test_lock_try_new_rejects_invalid_uri() {
+        match LockManager::try_new(vec!["not a valid redis uri"]) {
+            Err(LockError::Redis(_)) => (),
+            other => panic!("expected LockError::Redis, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_lock_no_servers_configured() {
+        let rl = LockManager::new(Vec::<String>::new());
+        let key = rl.get_unique_lock_id().unwrap();
+
+        match rl.lock(&key, 1000).await {
+            Err(LockError::NoServers) => (),
+            other => panic!("expected LockError::NoServers, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_lock_acquire_no_guard_no_servers_configured_bails() {
+        let rl = LockManager::new(Vec::<String>::new());
+        let key = rl.get_unique_lock_id().unwrap();
+
+        // Regression test: `acquire_no_guard` used to loop tightly forever
+        // on `NoServers` (no backoff sleep to yield on), hanging or pegging
+        // the CPU instead of bailing out. Bound it with a short timeout so a
+        // regression fails the test instead of hanging the suite.
+        let result = tokio::time::timeout(
+            tokio::time::Duration::from_secs(2),
+            rl.acquire_no_guard(&key, 1000),
+        )
+        .await
+        .expect("acquire_no_guard should bail instead of spinning forever");
+
+        match result {
+            Err(LockError::NoServers) => (),
+            other => panic!("expected LockError::NoServers, got {:?}", other),
+        }
+    }
+
+    #[cfg(feature = "tokio-comp")]
+    #[tokio::test]
+    async fn test_lock_acquire_no_servers_configured_bails() {
+        let rl = LockManager::new(Vec::<String>::new());
+        let key = rl.get_unique_lock_id().unwrap();
+
+        let result = tokio::time::timeout(tokio::time::Duration::from_secs(2), rl.acquire(&key, 1000))
+            .await
+            .expect("acquire should bail instead of spinning forever");
+
+        match result {
+            Err(LockError::NoServers) => (),
+            other => panic!("expected LockError::NoServers, got {:?}", other),
+        }
+    }
+
+    #[cfg(feature = "tokio-comp")]
+    #[tokio::test]
+    async fn test_lock_acquire_with_lease_no_servers_configured_bails() {
+        let rl = LockManager::new(Vec::<String>::new());
+        let key = rl.get_unique_lock_id().unwrap();
+
+        let result = tokio::time::timeout(
+            tokio::time::Duration::from_secs(2),
+            rl.acquire_with_lease(&key, 1000, None),
+        )
+        .await
+        .expect("acquire_with_lease should bail instead of spinning forever");
+
+        match result {
+            Err(LockError::NoServers) => (),
+            other => panic!("expected LockError::NoServers, got {:?}", other),
+        }
+    }
+
     #[tokio::test]
     async fn test_lock_direct_unlock_fails() -> Result<()> {
         let (_containers, addresses) = create_clients();
@@ -366,7 +993,7 @@ mod tests {
         let key = rl.get_unique_lock_id()?;
 
         let val = rl.get_unique_lock_id()?;
-        assert!(!rl.unlock_instance(&rl.servers[0], &key, &val).await);
+        assert!(!rl.unlock_instance(0, &key, &val).await);
 
         Ok(())
     }
@@ -382,7 +1009,7 @@ mod tests {
         let mut con = rl.servers[0].get_connection()?;
         redis::cmd("SET").arg(&*key).arg(&*val).execute(&mut con);
 
-        assert!(rl.unlock_instance(&rl.servers[0], &key, &val).await);
+        assert!(rl.unlock_instance(0, &key, &val).await);
 
         Ok(())
     }
@@ -398,10 +1025,7 @@ mod tests {
         let mut con = rl.servers[0].get_connection()?;
 
         redis::cmd("DEL").arg(&*key).execute(&mut con);
-        assert!(
-            rl.lock_instance(&rl.servers[0], &*key, val.clone(), 1000)
-                .await
-        );
+        assert!(rl.lock_instance(0, &*key, val.clone(), 1000).await.unwrap());
 
         Ok(())
     }
@@ -426,6 +1050,7 @@ mod tests {
             resource: key,
             val,
             validity_time: 0,
+            fencing_token: None,
         };
 
         rl.unlock(&lock).await;
@@ -457,6 +1082,97 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_lock_fencing_tokens_strictly_increase() -> Result<()> {
+        let (_containers, addresses) = create_clients();
+
+        let mut rl = LockManager::new(addresses.clone());
+        rl.set_fencing_tokens(true);
+
+        let key = rl.get_unique_lock_id()?;
+
+        let lock1 = rl.lock(&key, 200).await?;
+        let token1 = lock1.fencing_token.expect("fencing token should be set");
+
+        tokio::time::sleep(tokio::time::Duration::from_millis(300)).await;
+
+        let lock2 = rl.lock(&key, 200).await?;
+        let token2 = lock2.fencing_token.expect("fencing token should be set");
+
+        assert!(token2 > token1);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_lock_fencing_tokens_strictly_increase_with_imbalanced_counters() -> Result<()> {
+        // Regression test: tokens used to be `max` over each instance's own
+        // counter, so a node with a higher counter that simply wasn't part
+        // of a later quorum could make that later token look *smaller* than
+        // an earlier one. Pre-bump the counters on the non-designated nodes
+        // (index 1, 2) far past the designated node (index 0)'s counter,
+        // and confirm ordering still holds because only index 0 is consulted.
+        let (_containers, addresses) = create_clients();
+
+        let mut rl = LockManager::new(addresses.clone());
+        rl.set_fencing_tokens(true);
+
+        let key = rl.get_unique_lock_id()?;
+
+        for idx in [1usize, 2] {
+            let mut con = rl.servers[idx].get_connection()?;
+            let _: () = redis::cmd("SET")
+                .arg(LockManager::fencing_token_key(&key))
+                .arg(1_000_000)
+                .query(&mut con)
+                .unwrap();
+        }
+
+        let lock1 = rl.lock(&key, 200).await?;
+        let token1 = lock1.fencing_token.expect("fencing token should be set");
+        assert!(token1 < 1_000_000);
+
+        tokio::time::sleep(tokio::time::Duration::from_millis(300)).await;
+
+        let lock2 = rl.lock(&key, 200).await?;
+        let token2 = lock2.fencing_token.expect("fencing token should be set");
+
+        assert!(token2 > token1);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_lock_without_fencing_tokens_has_none() -> Result<()> {
+        let (_containers, addresses) = create_clients();
+
+        let rl = LockManager::new(addresses.clone());
+        let key = rl.get_unique_lock_id()?;
+
+        let lock = rl.lock(&key, 1000).await?;
+        assert_eq!(None, lock.fencing_token);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_lock_server_time_validity() -> Result<()> {
+        let (_containers, addresses) = create_clients();
+
+        let mut rl = LockManager::new(addresses.clone());
+        rl.set_server_time(true);
+
+        let key = rl.get_unique_lock_id()?;
+        let lock = rl.lock(&key, 1000).await?;
+        assert!(
+            lock.validity_time > 900,
+            "validity time: {}",
+            lock.validity_time
+        );
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_lock_lock_unlock() -> Result<()> {
         let (_containers, addresses) = create_clients();
@@ -487,6 +1203,75 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_lock_wait_for_lock_acquires_once_released() -> Result<()> {
+        let (_containers, addresses) = create_clients();
+
+        let rl1 = LockManager::new(addresses.clone());
+        let rl2 = LockManager::new(addresses.clone());
+        let key = rl1.get_unique_lock_id()?;
+
+        let lock1 = rl1.lock(&key, 500).await?;
+
+        let release = async {
+            tokio::time::sleep(tokio::time::Duration::from_millis(200)).await;
+            rl1.unlock(&lock1).await;
+        };
+        let wait = rl2.wait_for_lock(&key, 1000, tokio::time::Duration::from_secs(5));
+
+        let (_, lock2) = tokio::join!(release, wait);
+        assert!(lock2?.validity_time > 0);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_lock_wait_for_lock_honors_fencing_tokens() -> Result<()> {
+        let (_containers, addresses) = create_clients();
+
+        let mut rl1 = LockManager::new(addresses.clone());
+        rl1.set_fencing_tokens(true);
+        let mut rl2 = LockManager::new(addresses.clone());
+        rl2.set_fencing_tokens(true);
+        let key = rl1.get_unique_lock_id()?;
+
+        let lock1 = rl1.lock(&key, 500).await?;
+        let token1 = lock1.fencing_token.expect("fencing token should be set");
+
+        let release = async {
+            tokio::time::sleep(tokio::time::Duration::from_millis(200)).await;
+            rl1.unlock(&lock1).await;
+        };
+        let wait = rl2.wait_for_lock(&key, 1000, tokio::time::Duration::from_secs(5));
+
+        let (_, lock2) = tokio::join!(release, wait);
+        let token2 = lock2?.fencing_token.expect("fencing token should be set");
+        assert!(token2 > token1);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_lock_wait_for_lock_times_out() -> Result<()> {
+        let (_containers, addresses) = create_clients();
+
+        let rl1 = LockManager::new(addresses.clone());
+        let rl2 = LockManager::new(addresses.clone());
+        let key = rl1.get_unique_lock_id()?;
+
+        let _lock1 = rl1.lock(&key, 5000).await?;
+
+        match rl2
+            .wait_for_lock(&key, 1000, tokio::time::Duration::from_millis(300))
+            .await
+        {
+            Err(LockError::Unavailable) => (),
+            other => panic!("expected timeout, got {:?}", other),
+        }
+
+        Ok(())
+    }
+
     #[cfg(not(feature = "tokio-comp"))]
     #[tokio::test]
     async fn test_lock_lock_unlock_raii() -> Result<()> {
@@ -497,7 +1282,7 @@ mod tests {
         let key = rl.get_unique_lock_id()?;
 
         async {
-            let lock_guard = rl.acquire(&key, 1000).await;
+            let lock_guard = rl.acquire(&key, 1000).await.unwrap();
             let lock = &lock_guard.lock;
             assert!(
                 lock.validity_time > 900,
@@ -519,6 +1304,81 @@ mod tests {
         Ok(())
     }
 
+    #[cfg(feature = "tokio-comp")]
+    #[tokio::test]
+    async fn test_lock_lock_unlock_raii_release() -> Result<()> {
+        let (_containers, addresses) = create_clients();
+
+        let rl = LockManager::new(addresses.clone());
+        let rl2 = LockManager::new(addresses.clone());
+        let key = rl.get_unique_lock_id()?;
+
+        let lock_guard = rl.acquire(&key, 1000).await?;
+        assert!(lock_guard.lock.validity_time > 900);
+
+        if let Ok(_l) = rl2.lock(&key, 1000).await {
+            panic!("Lock acquired, even though it should be locked")
+        }
+
+        lock_guard.release().await;
+
+        match rl2.lock(&key, 1000).await {
+            Ok(l) => assert!(l.validity_time > 900),
+            Err(_) => panic!("Lock couldn't be acquired"),
+        }
+
+        Ok(())
+    }
+
+    #[cfg(feature = "tokio-comp")]
+    #[tokio::test]
+    async fn test_lock_lease_keeps_lock_alive_past_ttl() -> Result<()> {
+        let (_containers, addresses) = create_clients();
+
+        let rl = LockManager::new(addresses.clone());
+        let rl2 = LockManager::new(addresses.clone());
+        let key = rl.get_unique_lock_id()?;
+
+        let lease = rl.acquire_with_lease(&key, 300, None).await?;
+
+        // Outlive the original 300ms TTL; the watchdog should have renewed
+        // it at least once by now.
+        tokio::time::sleep(tokio::time::Duration::from_millis(700)).await;
+        assert!(lease.is_valid());
+
+        if let Ok(_l) = rl2.lock(&key, 1000).await {
+            panic!("Lock acquired, even though the lease should still be held")
+        }
+
+        drop(lease);
+
+        Ok(())
+    }
+
+    #[cfg(feature = "tokio-comp")]
+    #[tokio::test]
+    async fn test_lock_lease_drop_releases_lock() -> Result<()> {
+        let (_containers, addresses) = create_clients();
+
+        let rl = LockManager::new(addresses.clone());
+        let rl2 = LockManager::new(addresses.clone());
+        let key = rl.get_unique_lock_id()?;
+
+        let lease = rl.acquire_with_lease(&key, 5000, None).await?;
+        drop(lease);
+
+        // Dropping spawns the unlock rather than awaiting it, so give it a
+        // moment to run before checking the lock is actually free.
+        tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+
+        match rl2.lock(&key, 1000).await {
+            Ok(_) => (),
+            Err(_) => panic!("Lock still held after lease guard was dropped"),
+        }
+
+        Ok(())
+    }
+
     #[cfg(not(feature = "tokio-comp"))]
     #[tokio::test]
     async fn test_lock_extend_lock() -> Result<()> {
@@ -530,7 +1390,7 @@ mod tests {
         let key = rl1.get_unique_lock_id()?;
 
         async {
-            let lock1 = rl1.acquire(&key, 1000).await;
+            let lock1 = rl1.acquire(&key, 1000).await.unwrap();
 
             // Wait half a second before locking again
             tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
@@ -566,7 +1426,7 @@ mod tests {
 
         async {
             // Create 500ms lock and immediately extend 500ms
-            let lock1 = rl1.acquire(&key, 500).await;
+            let lock1 = rl1.acquire(&key, 500).await.unwrap();
             rl1.extend(&lock1.lock, 500).await.unwrap();
 
             // Wait one second for the lock to expire